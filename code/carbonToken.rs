@@ -2,47 +2,482 @@ module BasicCarbonOffsets::BCO {
     use 0x1::Signer;
     use 0x1::Account;
     use 0x1::Coin;
-    use 0x1::Event;
+    use 0x1::event;
     use 0x1::Vector;
-    
-    struct MintEvent has copy, drop, store {
+    use 0x1::Table;
+
+    struct MintEvent has drop, store {
         amount: u64,
         to: address,
     }
 
-    struct BurnEvent has copy, drop, store {
+    struct BurnEvent has drop, store {
         amount: u64,
         from: address,
     }
 
-    struct BCO has key {
-        balance: Coin.T<BCO>,
-        admin: address,
+    struct FreezeEvent has drop, store {
+        target: address,
+        frozen: bool,
+    }
+
+    // Emitted alongside MintEvent whenever a new batch is carved out, so
+    // indexers can reconstruct provenance (project/vintage/standard) without
+    // replaying every mint.
+    struct TokenDataCreationEvent has drop, store {
+        batch_id: u64,
+        project_id: vector<u8>,
+        vintage_year: u16,
+        registry: vector<u8>,
+        methodology: vector<u8>,
+        quantity: u64,
+    }
+
+    // Retiring an offset permanently claims it against emissions, which is
+    // semantically distinct from burning supply: it must record who
+    // benefited and over what serial range within the batch.
+    struct RetirementEvent has drop, store {
+        amount: u64,
+        from: address,
+        batch_id: u64,
+        beneficiary: vector<u8>,
+        note: vector<u8>,
+        serial_start: u64,
+        serial_end: u64,
+    }
+
+    // Emitted from `transfer`, the first way to move balances between
+    // holders without going through mint/burn/retire.
+    struct TransferEvent has drop, store {
+        from: address,
+        to: address,
+        amount: u64,
+    }
+
+    // Immutable receipt a holder can point a third party at to prove a
+    // specific retirement happened.
+    struct RetirementCertificate has store, copy, drop {
+        batch_id: u64,
+        amount: u64,
+        beneficiary: vector<u8>,
+        note: vector<u8>,
+        serial_start: u64,
+        serial_end: u64,
+    }
+
+    // Per-holder append-only log of retirement certificates.
+    struct RetirementLedger has key {
+        certificates: vector<RetirementCertificate>,
+    }
+
+    // A single minted lot. Unlike a plain fungible balance, this carries the
+    // registry metadata buyers need to verify which project, vintage and
+    // methodology backs the credits they hold.
+    //
+    // `quantity`/`retired` are bookkeeping on the batch itself, not a balance
+    // tied to any holder's coins: `burn` and `retire` take a `batch_id`
+    // alongside an amount withdrawn from the caller's ordinary fungible
+    // `BCO` balance, but nothing links those specific coins back to the lot
+    // they were minted in. A caller can draw down any batch they know the id
+    // of regardless of which batch their coins actually came from, so this
+    // is registry-level supply accounting, not a per-holder provenance
+    // guarantee.
+    struct CreditBatch has store {
+        id: u64,
+        project_id: vector<u8>,
+        vintage_year: u16,
+        registry: vector<u8>,
+        methodology: vector<u8>,
+        quantity: u64,
+        retired: u64,
+    }
+
+    // Phantom coin-type witness passed as the generic parameter to Coin's
+    // capability and mint/burn functions; carries no state of its own.
+    struct BCO {}
+
+    // Each capability is its own resource so the deployer can hand mint,
+    // freeze and burn authority to different holders (or reclaim it)
+    // independently instead of all-or-nothing. Calling the gated function
+    // requires holding the matching capability at your own address.
+    struct MintCap has key {
+        cap: Coin.MintCapability<BCO>,
+    }
+
+    struct FreezeCap has key {
+        cap: Coin.FreezeCapability<BCO>,
+    }
+
+    struct BurnCap has key {
+        cap: Coin.BurnCapability<BCO>,
     }
 
-    public fun initialize(account: &signer, initial_supply: u64) {
+    // Shared registry state that isn't gated by a single capability: the
+    // frozen-account list and the batch ledger are consulted by every
+    // capability holder, so they live at one well-known address (`owner`,
+    // set at `initialize` time) rather than inside any one capability.
+    struct Registry has key {
+        owner: address,
+        frozen_accounts: Table.T<address, bool>,
+        batches: Table.T<u64, CreditBatch>,
+        next_batch_id: u64,
+    }
+
+    public fun initialize(account: &signer, name: vector<u8>, symbol: vector<u8>, decimals: u8, initial_supply: u64) {
+        let (mint_cap, freeze_cap, burn_cap) = Coin.initialize<BCO>(
+            account,
+            name,
+            symbol,
+            decimals,
+            true,
+        );
         let admin = Signer.address_of(account);
-        let coin = Coin.mint<BCO>(initial_supply);
-        let bco = BCO {
-            balance: coin,
-            admin: admin,
-        };
-        move_to(account, bco);
+        let coin = Coin.mint<BCO>(initial_supply, &mint_cap);
+        Coin.deposit(admin, coin);
+        move_to(account, MintCap { cap: mint_cap });
+        move_to(account, FreezeCap { cap: freeze_cap });
+        move_to(account, BurnCap { cap: burn_cap });
+        move_to(account, Registry {
+            owner: admin,
+            frozen_accounts: Table.new<address, bool>(),
+            batches: Table.new<u64, CreditBatch>(),
+            next_batch_id: 0,
+        });
+    }
+
+    // Hands `MintCap` from `admin`'s own address to `to`, who must co-sign
+    // to accept it. Aborts if `admin` doesn't currently hold the cap.
+    public fun delegate_mint_cap(admin: &signer, to: &signer) acquires MintCap {
+        let cap = move_from<MintCap>(Signer.address_of(admin));
+        move_to(to, cap);
+    }
+
+    // Pulls `MintCap` back from `holder` to the registry owner, without
+    // needing `holder`'s cooperation.
+    public fun revoke_mint_cap(admin: &signer, registry_addr: address, holder: address) acquires MintCap, Registry {
+        let reg = borrow_global<Registry>(registry_addr);
+        assert!(Signer.address_of(admin) == reg.owner, 5);
+        let cap = move_from<MintCap>(holder);
+        move_to(admin, cap);
+    }
+
+    public fun delegate_freeze_cap(admin: &signer, to: &signer) acquires FreezeCap {
+        let cap = move_from<FreezeCap>(Signer.address_of(admin));
+        move_to(to, cap);
+    }
+
+    public fun revoke_freeze_cap(admin: &signer, registry_addr: address, holder: address) acquires FreezeCap, Registry {
+        let reg = borrow_global<Registry>(registry_addr);
+        assert!(Signer.address_of(admin) == reg.owner, 5);
+        let cap = move_from<FreezeCap>(holder);
+        move_to(admin, cap);
+    }
+
+    public fun delegate_burn_cap(admin: &signer, to: &signer) acquires BurnCap {
+        let cap = move_from<BurnCap>(Signer.address_of(admin));
+        move_to(to, cap);
     }
 
-    public fun mint(account: &signer, to: address, amount: u64) {
-        let bco = borrow_global_mut<BCO>(Signer.address_of(account));
-        assert!(Signer.address_of(account) == bco.admin, 1);
-        let coin = Coin.mint<BCO>(amount);
+    public fun revoke_burn_cap(admin: &signer, registry_addr: address, holder: address) acquires BurnCap, Registry {
+        let reg = borrow_global<Registry>(registry_addr);
+        assert!(Signer.address_of(admin) == reg.owner, 5);
+        let cap = move_from<BurnCap>(holder);
+        move_to(admin, cap);
+    }
+
+    // Mints `amount` of supply and carves it into a new provenance-tagged
+    // batch so the lot can be traced back to a project/vintage/standard.
+    // Returns the new batch id. The caller must hold `MintCap`; `registry_addr`
+    // is where the shared batch ledger and frozen-account list live.
+    public fun mint(
+        account: &signer,
+        registry_addr: address,
+        to: address,
+        amount: u64,
+        project_id: vector<u8>,
+        vintage_year: u16,
+        registry: vector<u8>,
+        methodology: vector<u8>,
+    ): u64 acquires MintCap, Registry {
+        let mint_cap = borrow_global<MintCap>(Signer.address_of(account));
+        let reg = borrow_global_mut<Registry>(registry_addr);
+        assert!(!is_frozen(reg, to), 2);
+
+        let coin = Coin.mint<BCO>(amount, &mint_cap.cap);
         Coin.deposit(to, coin);
-        Event::emit_event<MintEvent>(&bco.mint_events, MintEvent { amount, to });
+
+        let batch_id = reg.next_batch_id;
+        reg.next_batch_id = batch_id + 1;
+        Table.add(&mut reg.batches, batch_id, CreditBatch {
+            id: batch_id,
+            project_id,
+            vintage_year,
+            registry,
+            methodology,
+            quantity: amount,
+            retired: 0,
+        });
+
+        event::emit(MintEvent { amount, to });
+        event::emit(TokenDataCreationEvent {
+            batch_id,
+            project_id,
+            vintage_year,
+            registry,
+            methodology,
+            quantity: amount,
+        });
+        batch_id
+    }
+
+    // Burns `amount` out of circulation against a specific batch, rejecting
+    // any attempt to draw down more than that batch still holds. The caller
+    // must hold `BurnCap` and is burning their own balance.
+    public fun burn(account: &signer, registry_addr: address, batch_id: u64, amount: u64) acquires BurnCap, Registry {
+        assert!(amount > 0, 6);
+        let admin = Signer.address_of(account);
+        let burn_cap = borrow_global<BurnCap>(admin);
+        let reg = borrow_global_mut<Registry>(registry_addr);
+        assert!(!is_frozen(reg, admin), 3);
+
+        let batch = Table.borrow_mut(&mut reg.batches, batch_id);
+        assert!(batch.quantity >= amount, 4);
+        batch.quantity = batch.quantity - amount;
+
+        let coin = Coin.withdraw<BCO>(account, amount);
+        Coin.burn(coin, &burn_cap.cap);
+        event::emit(BurnEvent { amount, from: admin });
     }
 
-    public fun burn(account: &signer, amount: u64) {
-        let bco = borrow_global_mut<BCO>(Signer.address_of(account));
+    // Moves `amount` from the caller's own balance to `to`, rejecting the
+    // transfer if either side is frozen. Deliberately takes `registry_addr`
+    // in addition to `to`/`amount`/`account`: the frozen-account list lives
+    // in a `Registry` resource published under the BCO deployer's address,
+    // and Move has no way for this module to discover that address on its
+    // own (no global lookup by type the way some other chains' VMs allow),
+    // so the caller must supply it explicitly.
+    public fun transfer(account: &signer, registry_addr: address, to: address, amount: u64) acquires Registry {
         let from = Signer.address_of(account);
-        let coin = Coin.withdraw<BCO>(&bco.balance, amount);
-        Coin.burn(coin);
-        Event::emit_event<BurnEvent>(&bco.burn_events, BurnEvent { amount, from });
+        let reg = borrow_global<Registry>(registry_addr);
+        assert!(!is_frozen(reg, from), 3);
+        assert!(!is_frozen(reg, to), 2);
+
+        let coin = Coin.withdraw<BCO>(account, amount);
+        Coin.deposit(to, coin);
+        event::emit(TransferEvent { from, to, amount });
+    }
+
+    // Permanently retires `amount` from `batch_id` on behalf of `beneficiary`,
+    // the same way `burn` removes coins from circulation but additionally
+    // recording a certificate third parties can independently verify. Unlike
+    // `burn`, the caller doesn't need to hold `BurnCap` themselves: retiring
+    // your own balance is self-service, authorized by the registry's burn
+    // authority rather than a personally delegated one. `registry_addr` is
+    // the address holding the `Registry`/`BurnCap` for this batch.
+    public fun retire(
+        account: &signer,
+        registry_addr: address,
+        batch_id: u64,
+        amount: u64,
+        beneficiary: vector<u8>,
+        note: vector<u8>,
+    ) acquires BurnCap, Registry, RetirementLedger {
+        assert!(amount > 0, 6);
+        let from = Signer.address_of(account);
+        let burn_cap = borrow_global<BurnCap>(registry_addr);
+        let reg = borrow_global_mut<Registry>(registry_addr);
+        assert!(!is_frozen(reg, from), 3);
+
+        let batch = Table.borrow_mut(&mut reg.batches, batch_id);
+        assert!(batch.quantity >= amount, 4);
+        batch.quantity = batch.quantity - amount;
+        let serial_start = batch.retired;
+        let serial_end = serial_start + amount - 1;
+        batch.retired = batch.retired + amount;
+
+        let coin = Coin.withdraw<BCO>(account, amount);
+        Coin.burn(coin, &burn_cap.cap);
+
+        event::emit(RetirementEvent {
+            amount,
+            from,
+            batch_id,
+            beneficiary: copy beneficiary,
+            note: copy note,
+            serial_start,
+            serial_end,
+        });
+
+        if (!exists<RetirementLedger>(from)) {
+            move_to(account, RetirementLedger { certificates: Vector.empty<RetirementCertificate>() });
+        };
+        let ledger = borrow_global_mut<RetirementLedger>(from);
+        Vector.push_back(&mut ledger.certificates, RetirementCertificate {
+            batch_id,
+            amount,
+            beneficiary,
+            note,
+            serial_start,
+            serial_end,
+        });
+    }
+
+    // Number of retirement certificates recorded for `holder`, so a caller
+    // knows the valid index range for `get_certificate`.
+    public fun certificate_count(holder: address): u64 acquires RetirementLedger {
+        if (!exists<RetirementLedger>(holder)) {
+            0
+        } else {
+            Vector.length(&borrow_global<RetirementLedger>(holder).certificates)
+        }
+    }
+
+    // Lets any third party independently verify a holder's retirement claim.
+    public fun get_certificate(holder: address, index: u64): RetirementCertificate acquires RetirementLedger {
+        *Vector.borrow(&borrow_global<RetirementLedger>(holder).certificates, index)
+    }
+
+    // Halts a holder flagged for KYC/sanctions reasons without touching their
+    // balance. Possession of `FreezeCap` at the caller's own address is the
+    // authorization check itself (`borrow_global` aborts without it), so the
+    // power can be delegated to or revoked from a compliance operator via
+    // `delegate_freeze_cap`/`revoke_freeze_cap` without touching mint/burn.
+    public fun freeze_account(account: &signer, registry_addr: address, target: address) acquires FreezeCap, Registry {
+        let _freeze_cap = borrow_global<FreezeCap>(Signer.address_of(account));
+        let reg = borrow_global_mut<Registry>(registry_addr);
+        set_frozen(reg, target, true);
+    }
+
+    public fun unfreeze_account(account: &signer, registry_addr: address, target: address) acquires FreezeCap, Registry {
+        let _freeze_cap = borrow_global<FreezeCap>(Signer.address_of(account));
+        let reg = borrow_global_mut<Registry>(registry_addr);
+        set_frozen(reg, target, false);
+    }
+
+    fun set_frozen(reg: &mut Registry, target: address, frozen: bool) {
+        if (Table.contains(&reg.frozen_accounts, target)) {
+            *Table.borrow_mut(&mut reg.frozen_accounts, target) = frozen;
+        } else {
+            Table.add(&mut reg.frozen_accounts, target, frozen);
+        };
+        event::emit(FreezeEvent { target, frozen });
+    }
+
+    fun is_frozen(reg: &Registry, target: address): bool {
+        Table.contains(&reg.frozen_accounts, target) &&
+            *Table.borrow(&reg.frozen_accounts, target)
+    }
+
+    // Lets other modules (e.g. `Swap`) enforce the same freeze policy on
+    // their own withdraw/deposit paths without duplicating the registry's
+    // frozen-account bookkeeping.
+    public fun assert_not_frozen(registry_addr: address, target: address) acquires Registry {
+        let reg = borrow_global<Registry>(registry_addr);
+        assert!(!is_frozen(reg, target), 2);
+    }
+}
+
+module BasicCarbonOffsets::Swap {
+    use 0x1::Signer;
+    use 0x1::Coin;
+    use 0x1::event;
+    use 0x1::Hash;
+    use 0x1::Timestamp;
+    use BasicCarbonOffsets::BCO::{Self, BCO};
+
+    struct SwapLockedEvent has drop, store {
+        swap_addr: address,
+        hashlock: vector<u8>,
+        timelock: u64,
+        amount: u64,
+        recipient: address,
+        refund_to: address,
+    }
+
+    struct SwapClaimedEvent has drop, store {
+        swap_addr: address,
+        preimage: vector<u8>,
+    }
+
+    struct SwapRefundedEvent has drop, store {
+        swap_addr: address,
+    }
+
+    // Escrows BCO under the locker's own address until either the preimage
+    // of `hashlock` is revealed (claim) or `timelock` elapses (refund). The
+    // same preimage can gate a counter-leg on another chain, giving a
+    // trustless cross-chain swap with no custodian in between. `registry_addr`
+    // is recorded so `claim`/`refund` can re-check BCO's freeze policy
+    // without it being passed in again by whoever calls them.
+    struct Swap has key {
+        hashlock: vector<u8>,
+        timelock: u64,
+        recipient: address,
+        refund_to: address,
+        registry_addr: address,
+        coin: Coin.T<BCO>,
+    }
+
+    // Deposits `amount` of BCO under `account`'s address and records the
+    // hashlock/timelock terms. The swap is addressed by `account`'s own
+    // address for `claim`/`refund`. `registry_addr` is the BCO registry
+    // whose frozen-account list governs this escrow, since a frozen holder
+    // must not be able to withdraw into escrow any more than they could
+    // withdraw via `transfer`.
+    public fun lock(
+        account: &signer,
+        registry_addr: address,
+        hashlock: vector<u8>,
+        timelock: u64,
+        recipient: address,
+        refund_to: address,
+        amount: u64,
+    ) {
+        let swap_addr = Signer.address_of(account);
+        BCO::assert_not_frozen(registry_addr, swap_addr);
+        let coin = Coin.withdraw<BCO>(account, amount);
+
+        event::emit(SwapLockedEvent {
+            swap_addr,
+            hashlock: copy hashlock,
+            timelock,
+            amount,
+            recipient,
+            refund_to,
+        });
+
+        move_to(account, Swap {
+            hashlock,
+            timelock,
+            recipient,
+            refund_to,
+            registry_addr,
+            coin,
+        });
+    }
+
+    // Releases the escrowed coins to `recipient` iff `preimage` hashes to
+    // the stored hashlock and the timelock hasn't expired yet.
+    public fun claim(swap_addr: address, preimage: vector<u8>) acquires Swap {
+        assert!(exists<Swap>(swap_addr), 1);
+        let Swap { hashlock, timelock, recipient, refund_to: _, registry_addr, coin } = move_from<Swap>(swap_addr);
+        assert!(Hash.sha3_256(preimage) == hashlock, 2);
+        assert!(Timestamp.now_seconds() < timelock, 3);
+        BCO::assert_not_frozen(registry_addr, recipient);
+
+        Coin.deposit(recipient, coin);
+        event::emit(SwapClaimedEvent { swap_addr, preimage });
+    }
+
+    // Returns the escrowed coins to `refund_to` once the timelock has
+    // expired without a successful claim.
+    public fun refund(swap_addr: address) acquires Swap {
+        assert!(exists<Swap>(swap_addr), 1);
+        let Swap { hashlock: _, timelock, recipient: _, refund_to, registry_addr, coin } = move_from<Swap>(swap_addr);
+        assert!(Timestamp.now_seconds() >= timelock, 4);
+        BCO::assert_not_frozen(registry_addr, refund_to);
+
+        Coin.deposit(refund_to, coin);
+        event::emit(SwapRefundedEvent { swap_addr });
     }
 }